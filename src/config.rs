@@ -1,13 +1,44 @@
 use serde::Deserialize;
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 use std::error::Error;
+
+use crate::file_monitor::WatcherKind;
+
+fn default_watcher_backend() -> String {
+    "native".to_string()
+}
+
+fn default_poll_interval_ms() -> u64 {
+    1000
+}
+
+fn default_debounce_ms() -> u64 {
+    500
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Config {
     pub agent_id: String,
     pub check_interval_seconds: u64,
     pub server_url: Option<String>,
     pub paths_to_monitor: Vec<String>,
+    /// Watcher backend to use: "native" (default) or "poll".
+    #[serde(default = "default_watcher_backend")]
+    pub watcher_backend: String,
+    /// Poll interval used when `watcher_backend = "poll"`.
+    #[serde(default = "default_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+    /// Extra gitignore-style patterns to drop before an event reaches the channel,
+    /// on top of any `.gitignore`/`.ignore` files in a watched root or one of its
+    /// parent directories (ignore files in subdirectories of the root are not read).
+    #[serde(default)]
+    pub ignore_patterns: Vec<String>,
+    /// How long the debouncer waits for more changes before coalescing them
+    /// into a single batch of `FileSystemEvent`s.
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
 }
 
 impl Config {
@@ -16,4 +47,18 @@ impl Config {
         let config: Config = toml::from_str(&content)?;
         Ok(config)
     }
+
+    /// Resolve the configured watcher backend into a `WatcherKind`, falling back
+    /// to `Native` for unrecognized values.
+    pub fn watcher_kind(&self) -> WatcherKind {
+        match self.watcher_backend.as_str() {
+            "poll" => WatcherKind::Poll(Duration::from_millis(self.poll_interval_ms)),
+            _ => WatcherKind::Native,
+        }
+    }
+
+    /// The debounce window `FileMonitor` should coalesce raw events over.
+    pub fn debounce(&self) -> Duration {
+        Duration::from_millis(self.debounce_ms)
+    }
 }
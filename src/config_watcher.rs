@@ -0,0 +1,122 @@
+use crate::config::Config;
+use crate::file_monitor::{FileMonitor, WatcherKind};
+use log::{error, info, warn};
+use notify::{PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex};
+use tokio::time::sleep;
+
+/// Editors and some config-management tools write in chunks (truncate, then
+/// write), so a change event can fire before the new content is fully on
+/// disk. Debouncing by this much before re-reading keeps reload logic from
+/// tripping over a partial write.
+const RELOAD_DEBOUNCE: Duration = Duration::from_secs(1);
+
+/// Watches `config_path` for changes and hot-reloads `monitor`'s watched
+/// roots in place, without restarting the agent.
+///
+/// A parse failure (including one caused by reading mid-write) is logged and
+/// the previously running config is kept; the read is retried once before
+/// giving up, to ride out a truncate-then-write.
+pub async fn run_config_reloader(
+    config_path: PathBuf,
+    mut current: Config,
+    monitor: Arc<Mutex<FileMonitor>>,
+    watcher_kind: WatcherKind,
+) {
+    let (tx, mut rx) = mpsc::channel(16);
+
+    let watch_target = config_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."));
+
+    let _watcher = match start_fs_watcher(&watch_target, watcher_kind, tx) {
+        Ok(watcher) => watcher,
+        Err(e) => {
+            error!("Failed to watch {:?} for config reload: {}", watch_target, e);
+            return;
+        }
+    };
+
+    while rx.recv().await.is_some() {
+        // Debounce: keep draining until the channel is quiet for RELOAD_DEBOUNCE.
+        loop {
+            match tokio::time::timeout(RELOAD_DEBOUNCE, rx.recv()).await {
+                Ok(Some(())) => continue,
+                Ok(None) => return,
+                Err(_) => break,
+            }
+        }
+
+        match read_config_with_retry(&config_path).await {
+            Some(new_config) => {
+                reconcile(&monitor, &current, &new_config).await;
+                current = new_config;
+                info!("Reloaded config from {:?}", config_path);
+            }
+            None => warn!("Keeping previous config; reload of {:?} failed", config_path),
+        }
+    }
+}
+
+/// Watches `dir` for changes using the same backend the operator configured
+/// for `FileMonitor` (`Config::watcher_backend`), so `watcher_backend = "poll"`
+/// for a network FS/container/VM also applies to config hot-reload instead of
+/// silently keeping a native watcher that never fires there.
+fn start_fs_watcher(dir: &Path, kind: WatcherKind, tx: mpsc::Sender<()>) -> notify::Result<Box<dyn Watcher + Send>> {
+    let handler = move |res: notify::Result<notify::Event>| {
+        if res.is_ok() {
+            let _ = tx.blocking_send(());
+        }
+    };
+
+    let mut watcher: Box<dyn Watcher + Send> = match kind {
+        WatcherKind::Native => Box::new(RecommendedWatcher::new(handler, notify::Config::default())?),
+        WatcherKind::Poll(delay) => Box::new(PollWatcher::new(
+            handler,
+            notify::Config::default().with_poll_interval(delay),
+        )?),
+    };
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+async fn read_config_with_retry(path: &Path) -> Option<Config> {
+    match Config::load_settings(path) {
+        Ok(config) => return Some(config),
+        Err(e) => warn!("Failed to parse {:?} ({}); retrying once", path, e),
+    }
+
+    sleep(Duration::from_millis(200)).await;
+
+    match Config::load_settings(path) {
+        Ok(config) => Some(config),
+        Err(e) => {
+            error!("Failed to parse {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+async fn reconcile(monitor: &Arc<Mutex<FileMonitor>>, old: &Config, new: &Config) {
+    let old_paths: HashSet<&String> = old.paths_to_monitor.iter().collect();
+    let new_paths: HashSet<&String> = new.paths_to_monitor.iter().collect();
+
+    let mut monitor = monitor.lock().await;
+    for removed in old_paths.difference(&new_paths) {
+        if let Err(e) = monitor.unwatch(removed) {
+            error!("Failed to unwatch {}: {}", removed, e);
+        }
+    }
+    for added in new_paths.difference(&old_paths) {
+        if let Err(e) = monitor.watch(added) {
+            error!("Failed to watch {}: {}", added, e);
+        }
+    }
+
+    monitor.set_ignore_patterns(&new.paths_to_monitor, &new.ignore_patterns);
+}
@@ -1,31 +1,301 @@
-use notify::{RecommendedWatcher, RecursiveMode, Watcher, Event};
-use std::path::PathBuf;
-use std::sync::mpsc::{Sender};
+use notify::event::{CreateKind, RemoveKind};
+use notify::{EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use notify_debouncer_full::{new_debouncer_opt, DebounceEventResult, DebouncedEvent, Debouncer, FileIdMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::runtime::Handle;
+use tokio::sync::mpsc as tokio_mpsc;
 use log::{info, error};
 
-pub fn monitor_directories(
-    paths: &[String],
-    tx: Sender<Event>,
-) -> notify::Result<RecommendedWatcher> {
-    let mut watcher: RecommendedWatcher = notify::recommended_watcher({
-        let tx = tx.clone();
-        move |res| {
-            match res {
-                Ok(event) => {
-                    if let Err(e) = tx.send(event) {
-                        error!("Failed to send event: {}", e);
+use crate::ignore_filter::IgnoreMatcher;
+
+/// Which notify backend a `FileMonitor` should use to watch paths.
+///
+/// `Native` relies on OS-level notifications (inotify/FSEvents/ReadDirectoryChangesW)
+/// and is preferred when available. `Poll` re-scans watched paths on an interval,
+/// which is slower but works on network filesystems, containers, and VMs where
+/// native notifications aren't delivered reliably.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum WatcherKind {
+    #[default]
+    Native,
+    Poll(Duration),
+}
+
+/// A filesystem change normalized from the underlying watcher backend, after
+/// the debouncer has coalesced and rename-correlated the raw events.
+#[derive(Debug)]
+pub enum FileSystemEvent {
+    Created(PathBuf),
+    Modified(PathBuf),
+    Deleted(PathBuf),
+    Other(DebouncedEvent),
+}
+
+fn to_file_system_events(event: DebouncedEvent) -> Vec<FileSystemEvent> {
+    match event.kind {
+        EventKind::Create(_) => event.paths.iter().cloned().map(FileSystemEvent::Created).collect(),
+        EventKind::Modify(_) => event.paths.iter().cloned().map(FileSystemEvent::Modified).collect(),
+        EventKind::Remove(_) => event.paths.iter().cloned().map(FileSystemEvent::Deleted).collect(),
+        _ => vec![FileSystemEvent::Other(event)],
+    }
+}
+
+fn event_path(event: &FileSystemEvent) -> Option<&PathBuf> {
+    match event {
+        FileSystemEvent::Created(p) | FileSystemEvent::Modified(p) | FileSystemEvent::Deleted(p) => Some(p),
+        FileSystemEvent::Other(_) => None,
+    }
+}
+
+/// Whether the raw event kind already tells us if the path is a directory,
+/// so the ignore check doesn't have to re-derive it with a live `fs::metadata`
+/// call — which would miss for a `Deleted` event, since the path is already
+/// gone by the time it's handled.
+fn event_is_dir(kind: &EventKind) -> Option<bool> {
+    match kind {
+        EventKind::Create(CreateKind::Folder) => Some(true),
+        EventKind::Create(CreateKind::File) => Some(false),
+        EventKind::Remove(RemoveKind::Folder) => Some(true),
+        EventKind::Remove(RemoveKind::File) => Some(false),
+        _ => None,
+    }
+}
+
+/// Finds the matcher whose watched root is an ancestor of `path`, so events
+/// are tested against the ignore rules for the root they came from.
+fn matcher_for<'a>(matchers: &'a [IgnoreMatcher], path: &Path) -> Option<&'a IgnoreMatcher> {
+    matchers.iter().find(|m| path.starts_with(m.root()))
+}
+
+/// Prefix for `sync()`'s marker files. Checked so those events can bypass
+/// ignore filtering: an operator-supplied pattern like `.*` (exactly the kind
+/// of dotfile rule `ignore_patterns`/`.gitignore` commonly carries) would
+/// otherwise silently drop the cookie and make every `sync()` call time out.
+const SYNC_COOKIE_PREFIX: &str = ".sync-cookie-";
+
+fn is_sync_cookie(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with(SYNC_COOKIE_PREFIX))
+}
+
+fn build_matchers(paths: &[String], ignore_patterns: &[String]) -> Vec<IgnoreMatcher> {
+    paths
+        .iter()
+        .map(|p| IgnoreMatcher::new(&PathBuf::from(p), ignore_patterns))
+        .collect()
+}
+
+/// Backend-specific debouncer, wrapped in an enum the same way `WatcherKind`
+/// selects between them, since `Debouncer<T, _>` isn't trait-object friendly.
+enum DebouncerHandle {
+    Native(Debouncer<RecommendedWatcher, FileIdMap>),
+    Poll(Debouncer<PollWatcher, FileIdMap>),
+}
+
+impl DebouncerHandle {
+    fn watch(&mut self, path: &Path) -> notify::Result<()> {
+        match self {
+            DebouncerHandle::Native(d) => d.watcher().watch(path, RecursiveMode::Recursive),
+            DebouncerHandle::Poll(d) => d.watcher().watch(path, RecursiveMode::Recursive),
+        }
+    }
+
+    fn unwatch(&mut self, path: &Path) -> notify::Result<()> {
+        match self {
+            DebouncerHandle::Native(d) => d.watcher().unwatch(path),
+            DebouncerHandle::Poll(d) => d.watcher().unwatch(path),
+        }
+    }
+}
+
+/// Watches directories for filesystem changes and forwards de-duplicated,
+/// rename-correlated `FileSystemEvent`s onto a `tokio::sync::mpsc` channel.
+///
+/// Built around `notify-debouncer-full` instead of raw `notify` callbacks: a
+/// single save can otherwise produce duplicate Created/Modified events, and a
+/// `std::sync::mpsc` channel can't be awaited cleanly from Tokio. The
+/// debouncer's callback runs on its own thread, so construction captures a
+/// `tokio::runtime::Handle` and uses `handle.block_on(tx.send(..))` to hand
+/// events to the async receiver without it ever stalling.
+pub struct FileMonitor {
+    debouncer: DebouncerHandle,
+    matchers: Arc<RwLock<Vec<IgnoreMatcher>>>,
+    cookie_counter: AtomicU64,
+}
+
+impl FileMonitor {
+    /// Construct a `FileMonitor` watching `paths` with the given backend and
+    /// debounce window, forwarding normalized events to the returned channel.
+    /// Events matching `ignore_patterns` (or a `.gitignore`/`.ignore` file in
+    /// a watched root or one of its parent directories) are dropped before
+    /// they reach the channel; ignore files in subdirectories of a watched
+    /// root are not read. Must be called from within a Tokio runtime.
+    pub fn new(
+        paths: &[String],
+        kind: WatcherKind,
+        ignore_patterns: &[String],
+        debounce: Duration,
+    ) -> notify::Result<(FileMonitor, tokio_mpsc::Receiver<FileSystemEvent>)> {
+        let (tx, rx) = tokio_mpsc::channel(100);
+
+        let matchers = Arc::new(RwLock::new(build_matchers(paths, ignore_patterns)));
+
+        let runtime_handle = Handle::try_current()
+            .expect("FileMonitor::new must be called from within a Tokio runtime");
+
+        let event_handler = {
+            let matchers = matchers.clone();
+            move |result: DebounceEventResult| match result {
+                Ok(events) => {
+                    let matchers = matchers.read().unwrap();
+                    for event in events {
+                        let is_dir_hint = event_is_dir(&event.kind);
+                        for fs_event in to_file_system_events(event) {
+                            if let Some(path) = event_path(&fs_event) {
+                                if !is_sync_cookie(path) {
+                                    if let Some(matcher) = matcher_for(&matchers, path) {
+                                        let is_dir = is_dir_hint.unwrap_or_else(|| path.is_dir());
+                                        if matcher.is_ignored(path, is_dir) {
+                                            continue;
+                                        }
+                                    }
+                                }
+                            }
+                            if let Err(e) = runtime_handle.block_on(tx.send(fs_event)) {
+                                error!("Failed to send event: {}", e);
+                            }
+                        }
+                    }
+                }
+                Err(errors) => {
+                    for e in errors {
+                        error!("Watcher error: {}", e);
                     }
                 }
-                Err(e) => error!("Watcher error: {}", e),
             }
+        };
+
+        let mut debouncer = match kind {
+            WatcherKind::Native => DebouncerHandle::Native(new_debouncer_opt::<
+                _,
+                RecommendedWatcher,
+                FileIdMap,
+            >(
+                debounce,
+                None,
+                event_handler,
+                FileIdMap::new(),
+                notify::Config::default(),
+            )?),
+            WatcherKind::Poll(delay) => DebouncerHandle::Poll(new_debouncer_opt::<
+                _,
+                PollWatcher,
+                FileIdMap,
+            >(
+                debounce,
+                None,
+                event_handler,
+                FileIdMap::new(),
+                notify::Config::default().with_poll_interval(delay),
+            )?),
+        };
+
+        for path_str in paths {
+            let path = PathBuf::from(path_str);
+            debouncer.watch(&path)?;
+            info!("Started watching: {:?}", path);
         }
-    })?;
 
-    for path_str in paths {
-        let path = PathBuf::from(path_str);
-        watcher.watch(&path, RecursiveMode::Recursive)?;
-        info!("Started watching: {:?}", path);
+        Ok((
+            FileMonitor {
+                debouncer,
+                matchers,
+                cookie_counter: AtomicU64::new(0),
+            },
+            rx,
+        ))
+    }
+
+    /// Start watching an additional root, e.g. one added to `paths_to_monitor`
+    /// on a config reload.
+    pub fn watch(&mut self, path: &str) -> notify::Result<()> {
+        self.debouncer.watch(Path::new(path))?;
+        info!("Started watching: {}", path);
+        Ok(())
+    }
+
+    /// Stop watching a root, e.g. one removed from `paths_to_monitor` on a
+    /// config reload.
+    pub fn unwatch(&mut self, path: &str) -> notify::Result<()> {
+        self.debouncer.unwatch(Path::new(path))?;
+        info!("Stopped watching: {}", path);
+        Ok(())
+    }
+
+    /// Rebuild the ignore matchers for `paths` from `ignore_patterns`, e.g.
+    /// after a config reload changes either.
+    pub fn set_ignore_patterns(&self, paths: &[String], ignore_patterns: &[String]) {
+        *self.matchers.write().unwrap() = build_matchers(paths, ignore_patterns);
+    }
+
+    /// Checkpoint "every change up to now has been seen" without guessing with
+    /// a sleep: write a uniquely-named marker file into `root`, then collect
+    /// events from `rx` until the corresponding `Created` event for that exact
+    /// cookie path arrives. notify guarantees in-order delivery within a
+    /// watch, so once the cookie event is observed, every change made before
+    /// the cookie was written has already come through `rx`: it is among the
+    /// collected events unless the caller had received it earlier. Changes
+    /// made after the cookie was written may or may not be collected.
+    ///
+    /// Returns the events observed while waiting (including the cookie
+    /// itself), or an error if `timeout` elapses first. The cookie file is
+    /// removed afterwards either way.
+    pub async fn sync(
+        &self,
+        root: &Path,
+        rx: &mut tokio_mpsc::Receiver<FileSystemEvent>,
+        timeout: Duration,
+    ) -> std::io::Result<Vec<FileSystemEvent>> {
+        let cookie = self.cookie_counter.fetch_add(1, Ordering::Relaxed);
+        let cookie_path = root.join(format!("{}{}-{:x}", SYNC_COOKIE_PREFIX, cookie, random_suffix()));
+        std::fs::File::create(&cookie_path)?;
+
+        let collect = async {
+            let mut seen = Vec::new();
+            while let Some(event) = rx.recv().await {
+                let is_cookie = matches!(&event, FileSystemEvent::Created(p) if p == &cookie_path);
+                seen.push(event);
+                if is_cookie {
+                    return Some(seen);
+                }
+            }
+            None
+        };
+
+        let result = tokio::time::timeout(timeout, collect).await;
+        let _ = std::fs::remove_file(&cookie_path);
+
+        match result {
+            Ok(Some(events)) => Ok(events),
+            _ => Err(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                format!("Timed out waiting for sync cookie at {:?}", cookie_path),
+            )),
+        }
     }
+}
 
-    Ok(watcher) // Return to keep it alive in calling scope
-}
\ No newline at end of file
+/// Cheap, dependency-free uniqueness guard for cookie filenames: the counter
+/// alone is enough within one process, but this protects against collisions
+/// across process restarts that reset the counter to zero.
+fn random_suffix() -> u32 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    nanos ^ std::process::id()
+}
@@ -0,0 +1,108 @@
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use log::warn;
+use std::path::{Path, PathBuf};
+
+/// One ignore file's rules, rooted at the directory that declared them.
+struct IgnoreLayer {
+    gitignore: Gitignore,
+}
+
+/// Gitignore-style matcher for a single watched root.
+///
+/// Combines the operator-supplied `Config::ignore_patterns` with any
+/// `.gitignore`/`.ignore` files discovered by walking up from the root, the
+/// way watchexec discovers ignore files for a watch target. Each discovered
+/// file is kept as its own layer rooted at the directory that declared it —
+/// the way `ignore::WalkBuilder` roots a directory's own `.gitignore` — so an
+/// anchored pattern like `/foo` in an ancestor's `.gitignore` still means
+/// "foo directly under that ancestor", not "foo directly under the watched
+/// root". Layers are checked nearest-to-farthest and the first one with an
+/// opinion on a path wins, matching real gitignore semantics where a closer
+/// directory's rules take full precedence over a farther one's.
+pub struct IgnoreMatcher {
+    root: PathBuf,
+    layers: Vec<IgnoreLayer>,
+}
+
+impl IgnoreMatcher {
+    pub fn new(root: &Path, extra_patterns: &[String]) -> IgnoreMatcher {
+        let mut layers = Vec::new();
+
+        // The watched root's own ignore files plus the operator-supplied
+        // patterns are anchored at `root` and form the nearest (highest
+        // precedence) layer.
+        let mut root_builder = GitignoreBuilder::new(root);
+        add_ignore_files(&mut root_builder, root);
+        for pattern in extra_patterns {
+            if let Err(e) = root_builder.add_line(None, pattern) {
+                warn!("Invalid ignore pattern {:?}: {}", pattern, e);
+            }
+        }
+        layers.push(build_layer(root_builder, root));
+
+        // Each ancestor's ignore files get their own layer rooted at that
+        // ancestor, walking outward so nearer ancestors are checked first.
+        if let Some(parent) = root.parent() {
+            for ancestor in parent.ancestors() {
+                let mut builder = GitignoreBuilder::new(ancestor);
+                if add_ignore_files(&mut builder, ancestor) {
+                    layers.push(build_layer(builder, ancestor));
+                }
+            }
+        }
+
+        IgnoreMatcher {
+            root: root.to_path_buf(),
+            layers,
+        }
+    }
+
+    /// Whether `path` (absolute, or relative to the watched root) should be
+    /// dropped before it reaches the event channel. `is_dir` should reflect
+    /// what the originating event already knows (e.g. a `Remove` event's
+    /// kind), not a fresh `fs::metadata` call — by the time a `Deleted` event
+    /// is handled the path may no longer exist, which would make a
+    /// directory-only rule like `build/` fail to match.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        // Resolve against the watched root so ancestor layers, rooted at
+        // their own directory, see the path where it really is.
+        let path = self.root.join(path);
+        for layer in &self.layers {
+            match layer.gitignore.matched_path_or_any_parents(&path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => continue,
+            }
+        }
+        false
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+/// Adds `dir`'s own `.gitignore`/`.ignore` (if any) to `builder`. Returns
+/// whether either file was found, so callers can skip pushing an empty layer.
+fn add_ignore_files(builder: &mut GitignoreBuilder, dir: &Path) -> bool {
+    let mut found = false;
+    for name in [".gitignore", ".ignore"] {
+        let candidate = dir.join(name);
+        if candidate.is_file() {
+            found = true;
+            if let Some(e) = builder.add(&candidate) {
+                warn!("Failed to read ignore file {:?}: {}", candidate, e);
+            }
+        }
+    }
+    found
+}
+
+fn build_layer(builder: GitignoreBuilder, dir: &Path) -> IgnoreLayer {
+    let gitignore = builder.build().unwrap_or_else(|e| {
+        warn!("Failed to build ignore matcher for {:?}: {}", dir, e);
+        Gitignore::empty()
+    });
+    IgnoreLayer { gitignore }
+}
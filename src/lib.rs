@@ -1,22 +1,44 @@
-use tokio::sync::mpsc;
-use log::{info};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Mutex;
 
 pub mod config;
+pub mod config_watcher;
 pub mod file_monitor;
+pub mod ignore_filter;
+pub mod reporting;
 
-pub async fn main_logic() -> Result<(), Box<dyn std::error::Error>> {
-    let settings = config::Config::load_settings("config/settings.toml")?;
-
-    let (tx, mut rx) = mpsc::channel(100);
+const SETTINGS_PATH: &str = "config/settings.toml";
 
-    let _watcher = file_monitor::monitor_directories(&settings.paths_to_monitor, tx)?;
-
-    // Spawn background task to receive events
-    tokio::spawn(async move {
-        while let Some(event) = rx.recv().await {
-            info!("Event received: {:?}", event);
-        }
-    });
+pub async fn main_logic() -> Result<(), Box<dyn std::error::Error>> {
+    let settings = config::Config::load_settings(SETTINGS_PATH)?;
+
+    let (monitor, rx) = file_monitor::FileMonitor::new(
+        &settings.paths_to_monitor,
+        settings.watcher_kind(),
+        &settings.ignore_patterns,
+        settings.debounce(),
+    )?;
+    let monitor = Arc::new(Mutex::new(monitor));
+
+    // Spawn background task to batch and ship events to server_url (or just log them).
+    tokio::spawn(reporting::run_reporter(
+        rx,
+        settings.server_url.clone(),
+        settings.agent_id.clone(),
+        reporting::DEFAULT_BATCH_SIZE,
+        Duration::from_secs(settings.check_interval_seconds),
+    ));
+
+    // Hot-reload paths_to_monitor/ignore_patterns from settings.toml without restarting.
+    let watcher_kind = settings.watcher_kind();
+    tokio::spawn(config_watcher::run_config_reloader(
+        PathBuf::from(SETTINGS_PATH),
+        settings,
+        monitor.clone(),
+        watcher_kind,
+    ));
 
     // Keep the main task alive
     tokio::signal::ctrl_c().await?;
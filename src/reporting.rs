@@ -0,0 +1,178 @@
+use crate::file_monitor::FileSystemEvent;
+use log::{error, warn, info};
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::Receiver;
+use tokio::sync::{Mutex, Notify};
+use tokio::time::interval;
+
+/// Number of events collected before a batch is flushed early, regardless of
+/// `flush_interval`.
+pub const DEFAULT_BATCH_SIZE: usize = 100;
+
+/// How many un-sent batches are kept in memory while `server_url` is
+/// unreachable. Once full, the oldest batch is dropped so the queue keeps
+/// favoring the most recent events over stale ones.
+const MAX_QUEUED_BATCHES: usize = 50;
+
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+const MAX_SEND_ATTEMPTS: u32 = 5;
+
+#[derive(Debug, Serialize)]
+struct ReportedEvent {
+    path: PathBuf,
+    kind: &'static str,
+    agent_id: String,
+    timestamp: u64,
+}
+
+fn now_unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+fn to_reported_event(event: &FileSystemEvent, agent_id: &str) -> Option<ReportedEvent> {
+    let (kind, path) = match event {
+        FileSystemEvent::Created(p) => ("created", p.clone()),
+        FileSystemEvent::Modified(p) => ("modified", p.clone()),
+        FileSystemEvent::Deleted(p) => ("deleted", p.clone()),
+        FileSystemEvent::Other(_) => return None,
+    };
+    Some(ReportedEvent {
+        path,
+        kind,
+        agent_id: agent_id.to_string(),
+        timestamp: now_unix_secs(),
+    })
+}
+
+/// Bounded, drop-oldest queue of un-sent batches, shared between the
+/// consuming loop (which only ever pushes) and the sender task (which pops,
+/// and pushes back a batch it gave up on), so a slow/backing-off send never
+/// blocks event ingestion.
+struct FlushQueue {
+    batches: Mutex<VecDeque<Vec<ReportedEvent>>>,
+    notify: Notify,
+}
+
+impl FlushQueue {
+    fn new() -> Self {
+        FlushQueue {
+            batches: Mutex::new(VecDeque::new()),
+            notify: Notify::new(),
+        }
+    }
+
+    async fn push(&self, batch: Vec<ReportedEvent>) {
+        let mut batches = self.batches.lock().await;
+        batches.push_back(batch);
+        while batches.len() > MAX_QUEUED_BATCHES {
+            warn!("Report queue full; dropping oldest batch of events to favor recent ones");
+            batches.pop_front();
+        }
+        drop(batches);
+        self.notify.notify_one();
+    }
+
+    async fn pop(&self) -> Vec<ReportedEvent> {
+        loop {
+            if let Some(batch) = self.batches.lock().await.pop_front() {
+                return batch;
+            }
+            self.notify.notified().await;
+        }
+    }
+}
+
+/// Consumes `FileSystemEvent`s and ships them to `server_url` in batches.
+///
+/// Batches are flushed once they reach `batch_size` events or `flush_interval`
+/// elapses, whichever comes first. Flushing only hands the batch to a
+/// `FlushQueue` and returns immediately; a separate sender task drains that
+/// queue with exponential backoff, so a server outage (and the resulting
+/// retry sleeps) never stalls event ingestion. When `server_url` is `None`,
+/// events are only logged, matching the agent's original behavior.
+pub async fn run_reporter(
+    mut rx: Receiver<FileSystemEvent>,
+    server_url: Option<String>,
+    agent_id: String,
+    batch_size: usize,
+    flush_interval: Duration,
+) {
+    let Some(server_url) = server_url else {
+        while let Some(event) = rx.recv().await {
+            info!("Event received: {:?}", event);
+        }
+        return;
+    };
+
+    let client = reqwest::Client::new();
+    let queue = Arc::new(FlushQueue::new());
+    tokio::spawn(run_sender(client, server_url, queue.clone()));
+
+    let mut pending: Vec<ReportedEvent> = Vec::with_capacity(batch_size);
+    let mut ticker = interval(flush_interval);
+
+    loop {
+        tokio::select! {
+            maybe_event = rx.recv() => {
+                match maybe_event {
+                    Some(event) => {
+                        if let Some(reported) = to_reported_event(&event, &agent_id) {
+                            pending.push(reported);
+                        }
+                        if pending.len() >= batch_size {
+                            queue.push(std::mem::take(&mut pending)).await;
+                        }
+                    }
+                    None => {
+                        if !pending.is_empty() {
+                            queue.push(std::mem::take(&mut pending)).await;
+                        }
+                        break;
+                    }
+                }
+            }
+            _ = ticker.tick() => {
+                if !pending.is_empty() {
+                    queue.push(std::mem::take(&mut pending)).await;
+                }
+            }
+        }
+    }
+}
+
+/// Drains `queue` and ships each batch, retrying with backoff on failure.
+/// Runs as its own task so retry sleeps never block `run_reporter`'s event loop.
+async fn run_sender(client: reqwest::Client, server_url: String, queue: Arc<FlushQueue>) {
+    loop {
+        let batch = queue.pop().await;
+        if !send_with_retry(&client, &server_url, &batch).await {
+            warn!(
+                "Giving up on a batch after {} attempts; re-queueing behind newer events",
+                MAX_SEND_ATTEMPTS
+            );
+            queue.push(batch).await;
+        }
+    }
+}
+
+async fn send_with_retry(client: &reqwest::Client, server_url: &str, batch: &[ReportedEvent]) -> bool {
+    let mut backoff = INITIAL_BACKOFF;
+    for attempt in 1..=MAX_SEND_ATTEMPTS {
+        match client.post(server_url).json(batch).send().await {
+            Ok(resp) if resp.status().is_success() => return true,
+            Ok(resp) => error!("Server rejected event batch (attempt {}): {}", attempt, resp.status()),
+            Err(e) => error!("Failed to send event batch (attempt {}): {}", attempt, e),
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+    false
+}
@@ -1,4 +1,5 @@
 use endpoint_protection_agent::config::Config;
+use endpoint_protection_agent::file_monitor::WatcherKind;
 
 #[test]
 fn test_parse_config() {
@@ -28,3 +29,83 @@ fn test_parse_config_missing_optional_url() {
     assert_eq!(cfg.agent_id, "test-agent");
     assert_eq!(cfg.server_url, None);
 }
+
+#[test]
+fn test_parse_config_missing_ignore_patterns() {
+    let toml = r#"
+        agent_id = "test-agent"
+        check_interval_seconds = 5
+        paths_to_monitor = ["/tmp"]
+    "#;
+
+    let cfg: Config = toml::from_str(toml).unwrap();
+    assert!(cfg.ignore_patterns.is_empty());
+}
+
+#[test]
+fn test_parse_config_with_ignore_patterns() {
+    let toml = r#"
+        agent_id = "test-agent"
+        check_interval_seconds = 5
+        paths_to_monitor = ["/tmp"]
+        ignore_patterns = ["*.tmp", "!important.tmp"]
+    "#;
+
+    let cfg: Config = toml::from_str(toml).unwrap();
+    assert_eq!(cfg.ignore_patterns, vec!["*.tmp", "!important.tmp"]);
+}
+
+#[test]
+fn test_watcher_backend_defaults_to_native() {
+    let toml = r#"
+        agent_id = "test-agent"
+        check_interval_seconds = 5
+        paths_to_monitor = ["/tmp"]
+    "#;
+
+    let cfg: Config = toml::from_str(toml).unwrap();
+    assert_eq!(cfg.watcher_backend, "native");
+    assert!(matches!(cfg.watcher_kind(), WatcherKind::Native));
+}
+
+#[test]
+fn test_watcher_backend_poll_with_interval() {
+    let toml = r#"
+        agent_id = "test-agent"
+        check_interval_seconds = 5
+        paths_to_monitor = ["/tmp"]
+        watcher_backend = "poll"
+        poll_interval_ms = 2500
+    "#;
+
+    let cfg: Config = toml::from_str(toml).unwrap();
+    match cfg.watcher_kind() {
+        WatcherKind::Poll(delay) => assert_eq!(delay.as_millis(), 2500),
+        WatcherKind::Native => panic!("expected poll watcher kind"),
+    }
+}
+
+#[test]
+fn test_debounce_ms_defaults() {
+    let toml = r#"
+        agent_id = "test-agent"
+        check_interval_seconds = 5
+        paths_to_monitor = ["/tmp"]
+    "#;
+
+    let cfg: Config = toml::from_str(toml).unwrap();
+    assert_eq!(cfg.debounce(), std::time::Duration::from_millis(500));
+}
+
+#[test]
+fn test_debounce_ms_override() {
+    let toml = r#"
+        agent_id = "test-agent"
+        check_interval_seconds = 5
+        paths_to_monitor = ["/tmp"]
+        debounce_ms = 250
+    "#;
+
+    let cfg: Config = toml::from_str(toml).unwrap();
+    assert_eq!(cfg.debounce(), std::time::Duration::from_millis(250));
+}
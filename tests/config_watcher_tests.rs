@@ -0,0 +1,80 @@
+use endpoint_protection_agent::config::Config;
+use endpoint_protection_agent::config_watcher::run_config_reloader;
+use endpoint_protection_agent::file_monitor::{FileMonitor, FileSystemEvent, WatcherKind};
+use std::sync::Arc;
+use std::time::Duration;
+use tempfile::tempdir;
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+fn write_settings(path: &std::path::Path, paths_to_monitor: &[&std::path::Path]) {
+    let paths_toml = paths_to_monitor
+        .iter()
+        .map(|p| format!("\"{}\"", p.display()))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    std::fs::write(
+        path,
+        format!(
+            "agent_id = \"test-agent\"\ncheck_interval_seconds = 5\npaths_to_monitor = [{}]\n",
+            paths_toml
+        ),
+    )
+    .expect("Failed to write settings.toml");
+}
+
+#[tokio::test]
+async fn test_reload_starts_watching_newly_added_path() {
+    let settings_dir = tempdir().expect("Failed to create settings dir");
+    let settings_path = settings_dir.path().join("settings.toml");
+
+    let watched_dir = tempdir().expect("Failed to create watched dir");
+    let new_dir = tempdir().expect("Failed to create new dir");
+
+    write_settings(&settings_path, &[watched_dir.path()]);
+    let initial_config = Config::load_settings(&settings_path).expect("Failed to parse initial config");
+
+    let (monitor, mut rx) = FileMonitor::new(
+        &initial_config.paths_to_monitor,
+        WatcherKind::Native,
+        &[],
+        Duration::from_millis(100),
+    )
+    .expect("Failed to create FileMonitor");
+    let monitor = Arc::new(Mutex::new(monitor));
+
+    tokio::spawn(run_config_reloader(
+        settings_path.clone(),
+        initial_config,
+        monitor.clone(),
+        WatcherKind::Native,
+    ));
+
+    // Give the config watcher a moment to start before we rewrite the file.
+    sleep(Duration::from_millis(200)).await;
+
+    write_settings(&settings_path, &[watched_dir.path(), new_dir.path()]);
+
+    // Allow time for the ~1s reload debounce plus reconciliation.
+    sleep(Duration::from_secs(2)).await;
+
+    let probe_file = new_dir.path().join("probe.txt");
+    std::fs::write(&probe_file, "hello").expect("Failed to write probe file");
+
+    // Instead of guessing how long the debouncer/filesystem need with another
+    // sleep, checkpoint with a sync cookie: once it's observed, the probe
+    // file's event is guaranteed to already be in `events`.
+    let events = monitor
+        .lock()
+        .await
+        .sync(new_dir.path(), &mut rx, Duration::from_secs(5))
+        .await
+        .expect("sync should observe the cookie event");
+
+    let saw_probe_event = events
+        .iter()
+        .any(|event| matches!(event, FileSystemEvent::Created(p) if p == &probe_file));
+
+    assert!(saw_probe_event, "Expected an event from the newly-watched directory after reload");
+}
@@ -0,0 +1,68 @@
+use endpoint_protection_agent::file_monitor::{FileMonitor, FileSystemEvent, WatcherKind};
+use std::time::Duration;
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_sync_observes_cookie_and_cleans_up() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let (monitor, mut rx) = FileMonitor::new(
+        &[temp_dir.path().to_str().unwrap().to_string()],
+        WatcherKind::Native,
+        &[],
+        Duration::from_millis(100),
+    )
+    .expect("Failed to create FileMonitor");
+
+    let events = monitor
+        .sync(temp_dir.path(), &mut rx, Duration::from_secs(5))
+        .await
+        .expect("sync should observe its own cookie event");
+
+    assert!(!events.is_empty());
+    assert!(matches!(events.last(), Some(FileSystemEvent::Created(_))));
+
+    let leftover_cookies = std::fs::read_dir(temp_dir.path())
+        .expect("Failed to read temp dir")
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(".sync-cookie-"))
+        .count();
+    assert_eq!(leftover_cookies, 0, "sync() should remove its cookie file after observing it");
+}
+
+#[tokio::test]
+async fn test_sync_ignores_dotfile_ignore_pattern() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let (monitor, mut rx) = FileMonitor::new(
+        &[temp_dir.path().to_str().unwrap().to_string()],
+        WatcherKind::Native,
+        &[".*".to_string()],
+        Duration::from_millis(100),
+    )
+    .expect("Failed to create FileMonitor");
+
+    monitor
+        .sync(temp_dir.path(), &mut rx, Duration::from_secs(5))
+        .await
+        .expect("sync's cookie should bypass an ignore_patterns rule that would otherwise match dotfiles");
+}
+
+#[tokio::test]
+async fn test_sync_uses_distinct_cookies_across_calls() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let (monitor, mut rx) = FileMonitor::new(
+        &[temp_dir.path().to_str().unwrap().to_string()],
+        WatcherKind::Native,
+        &[],
+        Duration::from_millis(100),
+    )
+    .expect("Failed to create FileMonitor");
+
+    monitor
+        .sync(temp_dir.path(), &mut rx, Duration::from_secs(5))
+        .await
+        .expect("first sync should succeed");
+    monitor
+        .sync(temp_dir.path(), &mut rx, Duration::from_secs(5))
+        .await
+        .expect("second sync should succeed with a distinct cookie name");
+}
@@ -1,35 +1,67 @@
-use std::sync::mpsc::channel;
+use endpoint_protection_agent::file_monitor::{FileMonitor, FileSystemEvent, WatcherKind};
+use std::fs::{remove_file, File};
 use std::time::Duration;
-use std::fs::{File, remove_file};
-use std::thread::sleep;
-
-use endpoint_protection_agent::file_monitor::monitor_directories;
 use tempfile::tempdir;
 
-#[test]
-fn test_monitor_detects_file_creation() {
+#[tokio::test]
+async fn test_monitor_detects_file_creation() {
     let temp_dir = tempdir().expect("Failed to create temp dir");
     let dir_path = temp_dir.path().to_str().unwrap().to_string();
 
-    let (tx, rx) = channel();
-
-    let _watcher = monitor_directories(&[dir_path.clone()], tx)
-        .expect("Failed to start monitor");
+    let (monitor, mut rx) = FileMonitor::new(
+        &[dir_path],
+        WatcherKind::Native,
+        &[],
+        Duration::from_millis(100),
+    )
+    .expect("Failed to start monitor");
 
-    // Give time for watcher to initialize
-    sleep(Duration::from_secs(1));
-
-    // Trigger a file event
     let file_path = temp_dir.path().join("test_file.txt");
     let _ = File::create(&file_path).expect("Failed to create test file");
 
-    // Wait and check for event
-    let event = rx.recv_timeout(Duration::from_secs(3));
+    let events = monitor
+        .sync(temp_dir.path(), &mut rx, Duration::from_secs(5))
+        .await
+        .expect("Did not observe sync cookie after file creation");
+
     assert!(
-        event.is_ok(),
-        "Did not receive file system event on file creation"
+        events
+            .iter()
+            .any(|event| matches!(event, FileSystemEvent::Created(p) if p == &file_path)),
+        "Did not receive FileSystemEvent::Created for {}",
+        file_path.display()
     );
 
-    // Clean up
     remove_file(file_path).ok();
 }
+
+#[tokio::test]
+async fn test_rapid_writes_are_debounced_into_one_event() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let dir_path = temp_dir.path().to_str().unwrap().to_string();
+
+    let (monitor, mut rx) = FileMonitor::new(
+        &[dir_path],
+        WatcherKind::Native,
+        &[],
+        Duration::from_millis(300),
+    )
+    .expect("Failed to start monitor");
+
+    let file_path = temp_dir.path().join("rapid.txt");
+    for i in 0..5 {
+        std::fs::write(&file_path, format!("write {}", i)).expect("Failed to write test file");
+    }
+
+    let events = monitor
+        .sync(temp_dir.path(), &mut rx, Duration::from_secs(5))
+        .await
+        .expect("Did not observe sync cookie after rapid writes");
+
+    let matching = events
+        .iter()
+        .filter(|event| matches!(event, FileSystemEvent::Created(p) | FileSystemEvent::Modified(p) if p == &file_path))
+        .count();
+
+    assert_eq!(matching, 1, "Expected rapid writes to the same file to coalesce into a single event");
+}
@@ -0,0 +1,83 @@
+use endpoint_protection_agent::ignore_filter::IgnoreMatcher;
+use std::fs;
+use tempfile::tempdir;
+
+#[test]
+fn test_extra_pattern_is_ignored() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let matcher = IgnoreMatcher::new(temp_dir.path(), &["*.tmp".to_string()]);
+
+    assert!(matcher.is_ignored(&temp_dir.path().join("scratch.tmp"), false));
+    assert!(!matcher.is_ignored(&temp_dir.path().join("scratch.rs"), false));
+}
+
+#[test]
+fn test_negation_re_includes_file() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    let patterns = vec!["*.tmp".to_string(), "!important.tmp".to_string()];
+    let matcher = IgnoreMatcher::new(temp_dir.path(), &patterns);
+
+    assert!(matcher.is_ignored(&temp_dir.path().join("scratch.tmp"), false));
+    assert!(!matcher.is_ignored(&temp_dir.path().join("important.tmp"), false));
+}
+
+#[test]
+fn test_discovers_gitignore_under_root() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join(".gitignore"), "*.log\n").expect("Failed to write .gitignore");
+
+    let matcher = IgnoreMatcher::new(temp_dir.path(), &[]);
+
+    assert!(matcher.is_ignored(&temp_dir.path().join("agent.log"), false));
+    assert!(!matcher.is_ignored(&temp_dir.path().join("agent.rs"), false));
+}
+
+#[test]
+fn test_nearer_gitignore_wins_over_ancestor() {
+    let outer = tempdir().expect("Failed to create outer temp dir");
+    let inner = outer.path().join("inner");
+    fs::create_dir(&inner).expect("Failed to create inner dir");
+
+    fs::write(outer.path().join(".gitignore"), "!foo.log\n").expect("Failed to write outer .gitignore");
+    fs::write(inner.join(".gitignore"), "foo.log\n").expect("Failed to write inner .gitignore");
+
+    let matcher = IgnoreMatcher::new(&inner, &[]);
+
+    assert!(
+        matcher.is_ignored(&inner.join("foo.log"), false),
+        "the watched root's own .gitignore should take precedence over a less-specific ancestor"
+    );
+}
+
+#[test]
+fn test_ancestor_anchored_pattern_is_rooted_at_ancestor_not_watched_root() {
+    let outer = tempdir().expect("Failed to create outer temp dir");
+    let watched = outer.path().join("watched");
+    fs::create_dir(&watched).expect("Failed to create watched dir");
+
+    // Anchored at `outer`, so this should only ever match `outer/secret.txt`,
+    // never `outer/watched/secret.txt`.
+    fs::write(outer.path().join(".gitignore"), "/secret.txt\n").expect("Failed to write outer .gitignore");
+
+    let matcher = IgnoreMatcher::new(&watched, &[]);
+
+    assert!(
+        !matcher.is_ignored(&watched.join("secret.txt"), false),
+        "an ancestor's anchored pattern must stay rooted at the ancestor, not the watched root"
+    );
+}
+
+#[test]
+fn test_directory_only_pattern_matches_deleted_directory() {
+    let temp_dir = tempdir().expect("Failed to create temp dir");
+    fs::write(temp_dir.path().join(".gitignore"), "build/\n").expect("Failed to write .gitignore");
+
+    let matcher = IgnoreMatcher::new(temp_dir.path(), &[]);
+    let build_dir = temp_dir.path().join("build");
+
+    // The directory no longer exists on disk, the way it wouldn't by the
+    // time a `Deleted` event for it is handled; `is_dir` must come from the
+    // caller, not a live `fs::metadata` check, for the directory-only rule to
+    // still match.
+    assert!(matcher.is_ignored(&build_dir, true));
+}